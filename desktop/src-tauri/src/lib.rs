@@ -1,83 +1,41 @@
+mod logs;
+mod supervisor;
+mod tunnel;
 mod window_customizer;
+mod workspace;
 
 use std::{
-    collections::VecDeque,
     net::{SocketAddr, TcpListener},
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 use tauri::{AppHandle, LogicalSize, Manager, RunEvent, WebviewUrl, WebviewWindow, path::BaseDirectory};
-use tauri_plugin_clipboard_manager::ClipboardExt;
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogResult};
 use tauri_plugin_shell::process::{CommandChild, CommandEvent};
 use tauri_plugin_shell::ShellExt;
 use tokio::net::TcpSocket;
 
+use crate::logs::{clear_logs, copy_logs_to_clipboard, get_logs, LogState};
+use crate::supervisor::{get_sidecar_status, SupervisorState};
+use crate::tunnel::{tunnel_connect, tunnel_disconnect, tunnel_status, TunnelState, TunnelStatus};
 use crate::window_customizer::PinchZoomDisablePlugin;
+use crate::workspace::{close_workspace, list_workspaces, open_workspace, set_active_workspace, ServerRegistry};
 
-#[derive(Clone)]
-struct ServerState(Arc<Mutex<Option<CommandChild>>>);
-
-#[derive(Clone)]
-struct LogState(Arc<Mutex<VecDeque<String>>>);
-
-const MAX_LOG_ENTRIES: usize = 200;
 const DEFAULT_SKILLS_PORT: u32 = 4097;
 
 #[tauri::command]
 fn kill_sidecar(app: AppHandle) {
-    let Some(server_state) = app.try_state::<ServerState>() else {
+    let Some(registry) = app.try_state::<ServerRegistry>() else {
         println!("Server not running");
         return;
     };
 
-    let Some(server_state) = server_state
-        .0
-        .lock()
-        .expect("Failed to acquire mutex lock")
-        .take()
-    else {
-        println!("Server state missing");
-        return;
-    };
-
-    let _ = server_state.kill();
+    registry.kill_all();
 
     println!("Killed server");
 }
 
-#[tauri::command]
-async fn copy_logs_to_clipboard(app: AppHandle) -> Result<(), String> {
-    let log_state = app.try_state::<LogState>().ok_or("Log state not found")?;
-
-    let logs = log_state
-        .0
-        .lock()
-        .map_err(|_| "Failed to acquire log lock")?;
-
-    let log_text = logs.iter().cloned().collect::<Vec<_>>().join("");
-
-    app.clipboard()
-        .write_text(log_text)
-        .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
-
-    Ok(())
-}
-
-#[tauri::command]
-async fn get_logs(app: AppHandle) -> Result<String, String> {
-    let log_state = app.try_state::<LogState>().ok_or("Log state not found")?;
-
-    let logs = log_state
-        .0
-        .lock()
-        .map_err(|_| "Failed to acquire log lock")?;
-
-    Ok(logs.iter().cloned().collect::<Vec<_>>().join(""))
-}
-
-fn env_string(key: &str) -> Option<String> {
+pub(crate) fn env_string(key: &str) -> Option<String> {
     std::env::var(key)
         .ok()
         .map(|value| value.trim().to_string())
@@ -88,7 +46,7 @@ fn env_port(key: &str) -> Option<u32> {
     env_string(key).and_then(|value| value.parse().ok())
 }
 
-fn find_free_port() -> u32 {
+pub(crate) fn find_free_port() -> u32 {
     TcpListener::bind("127.0.0.1:0")
         .expect("Failed to bind to find free port")
         .local_addr()
@@ -174,9 +132,17 @@ fn get_user_shell() -> String {
     std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string())
 }
 
-fn spawn_sidecar(app: &AppHandle, port: u32, skills_port: u32) -> CommandChild {
-    let log_state = app.state::<LogState>();
-    let log_state_clone = log_state.inner().clone();
+/// Spawns the `opencode-cli` sidecar and returns its handle along with the
+/// raw event stream. The caller is responsible for forwarding stdout/stderr
+/// into `LogState` and for reacting to `CommandEvent::Terminated`. `cwd`
+/// lets a workspace's sidecar run with its project directory as the
+/// working directory instead of the app's own.
+pub(crate) fn spawn_sidecar_process(
+    app: &AppHandle,
+    port: u32,
+    skills_port: u32,
+    cwd: Option<&Path>,
+) -> (tokio::sync::mpsc::Receiver<CommandEvent>, CommandChild) {
     let config_override = build_opencode_config_content();
 
     let state_dir = app
@@ -196,6 +162,9 @@ fn spawn_sidecar(app: &AppHandle, port: u32, skills_port: u32) -> CommandChild {
         if let Some(config_override) = config_override.as_ref() {
             command.env("OPENCODE_CONFIG_CONTENT", config_override);
         }
+        if let Some(cwd) = cwd {
+            command.current_dir(cwd);
+        }
         command
             .args(["serve", &format!("--port={port}")])
             .spawn()
@@ -220,6 +189,9 @@ fn spawn_sidecar(app: &AppHandle, port: u32, skills_port: u32) -> CommandChild {
         if let Some(config_override) = config_override.as_ref() {
             command.env("OPENCODE_CONFIG_CONTENT", config_override);
         }
+        if let Some(cwd) = cwd {
+            command.current_dir(cwd);
+        }
         command
             .args([
                 "-il",
@@ -230,41 +202,7 @@ fn spawn_sidecar(app: &AppHandle, port: u32, skills_port: u32) -> CommandChild {
             .expect("Failed to spawn opencode")
     };
 
-    tauri::async_runtime::spawn(async move {
-        while let Some(event) = rx.recv().await {
-            match event {
-                CommandEvent::Stdout(line_bytes) => {
-                    let line = String::from_utf8_lossy(&line_bytes);
-                    print!("{line}");
-
-                    // Store log in shared state
-                    if let Ok(mut logs) = log_state_clone.0.lock() {
-                        logs.push_back(format!("[STDOUT] {}", line));
-                        // Keep only the last MAX_LOG_ENTRIES
-                        while logs.len() > MAX_LOG_ENTRIES {
-                            logs.pop_front();
-                        }
-                    }
-                }
-                CommandEvent::Stderr(line_bytes) => {
-                    let line = String::from_utf8_lossy(&line_bytes);
-                    eprint!("{line}");
-
-                    // Store log in shared state
-                    if let Ok(mut logs) = log_state_clone.0.lock() {
-                        logs.push_back(format!("[STDERR] {}", line));
-                        // Keep only the last MAX_LOG_ENTRIES
-                        while logs.len() > MAX_LOG_ENTRIES {
-                            logs.pop_front();
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-    });
-
-    child
+    (rx, child)
 }
 
 async fn is_server_running(port: u32) -> bool {
@@ -296,27 +234,44 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             kill_sidecar,
             copy_logs_to_clipboard,
-            get_logs
+            get_logs,
+            clear_logs,
+            tunnel_connect,
+            tunnel_status,
+            tunnel_disconnect,
+            get_sidecar_status,
+            open_workspace,
+            list_workspaces,
+            close_workspace,
+            set_active_workspace
         ])
         .setup(move |app| {
             let app = app.handle().clone();
 
             // Initialize log state
-            app.manage(LogState(Arc::new(Mutex::new(VecDeque::new()))));
+            app.manage(LogState::new(&app));
+            app.manage(TunnelState::new());
+            app.manage(SupervisorState::new());
+            app.manage(ServerRegistry::new());
 
             tauri::async_runtime::spawn(async move {
                 let base_override = get_opencode_base_override();
                 let skills_base_override = get_skills_base_override();
+                let tunnel_config = tunnel::get_tunnel_config();
 
-                let port = if base_override.is_some() {
+                let mut port = if tunnel_config.is_some() || base_override.is_some() {
                     None
                 } else {
                     Some(get_sidecar_port())
                 };
 
-                let should_spawn_sidecar = match port {
-                    Some(port) => !is_server_running(port).await,
-                    None => false,
+                let should_spawn_sidecar = if tunnel_config.is_some() {
+                    false
+                } else {
+                    match port {
+                        Some(port) => !is_server_running(port).await,
+                        None => false,
+                    }
                 };
 
                 let skills_port_override = get_skills_port_override();
@@ -335,8 +290,22 @@ pub fn run() {
                     }
                 }
 
-                let child = if should_spawn_sidecar {
-                    let child = spawn_sidecar(&app, port.expect("Sidecar port missing"), skills_port);
+                let registry = app.state::<ServerRegistry>().inner().clone();
+
+                if should_spawn_sidecar {
+                    let sidecar_port = port.expect("Sidecar port missing");
+                    let cwd = std::env::current_dir().unwrap_or_default();
+                    let (rx, child) = spawn_sidecar_process(&app, sidecar_port, skills_port, None);
+                    let session_id = registry.insert(child, sidecar_port, skills_port, cwd);
+                    let handle = supervisor::watch(
+                        app.clone(),
+                        registry.clone(),
+                        session_id.clone(),
+                        sidecar_port,
+                        skills_port,
+                        rx,
+                    );
+                    registry.set_supervisor_handle(&session_id, handle);
 
                     let timestamp = Instant::now();
                     loop {
@@ -348,7 +317,7 @@ pub fn run() {
                               .blocking_show_with_result();
 
                             if matches!(&res, MessageDialogResult::Custom(name) if name == "Copy Logs And Exit") {
-                                match copy_logs_to_clipboard(app.clone()).await {
+                                match copy_logs_to_clipboard(app.clone(), None).await {
                                     Ok(()) => println!("Logs copied to clipboard successfully"),
                                     Err(e) => println!("Failed to copy logs to clipboard: {}", e),
                                 }
@@ -370,11 +339,60 @@ pub fn run() {
                     }
 
                     println!("Server ready after {:?}", timestamp.elapsed());
+                }
 
-                    Some(child)
-                } else {
-                    None
-                };
+                let tunnel_mode = tunnel_config.is_some();
+
+                if let Some(tunnel_config) = tunnel_config {
+                    let forwarded_port = match tunnel_connect(
+                        app.clone(),
+                        tunnel_config.relay_addr,
+                        tunnel_config.token,
+                    )
+                    .await
+                    {
+                        Ok(TunnelStatus::Connected { forwarded_port }) => forwarded_port,
+                        _ => {
+                            let res = app.dialog()
+                              .message("Failed to connect to the remote OpenCode server over the tunnel. Copy logs using the button below and send them to the team for assistance.")
+                              .title("Startup Failed")
+                              .buttons(MessageDialogButtons::OkCancelCustom("Copy Logs And Exit".to_string(), "Exit".to_string()))
+                              .blocking_show_with_result();
+
+                            if matches!(&res, MessageDialogResult::Custom(name) if name == "Copy Logs And Exit") {
+                                match copy_logs_to_clipboard(app.clone(), None).await {
+                                    Ok(()) => println!("Logs copied to clipboard successfully"),
+                                    Err(e) => println!("Failed to copy logs to clipboard: {}", e),
+                                }
+                            }
+
+                            app.exit(1);
+
+                            return;
+                        }
+                    };
+
+                    let timestamp = Instant::now();
+                    while !is_server_running(forwarded_port).await {
+                        if timestamp.elapsed() > Duration::from_secs(7) {
+                            app.dialog()
+                                .message("Timed out waiting for the remote OpenCode server to become reachable through the tunnel.")
+                                .title("Startup Failed")
+                                .buttons(MessageDialogButtons::Ok)
+                                .blocking_show();
+
+                            app.exit(1);
+
+                            return;
+                        }
+
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                    }
+
+                    println!("Tunnel ready after {:?}", timestamp.elapsed());
+
+                    port = Some(forwarded_port);
+                }
 
                 let primary_monitor = app.primary_monitor().ok().flatten();
                 let size = primary_monitor
@@ -384,9 +402,18 @@ pub fn run() {
                 let base_url = base_override
                     .clone()
                     .or_else(|| port.map(|value| format!("http://127.0.0.1:{value}")));
-                let skills_base_url = skills_base_override
-                    .clone()
-                    .or_else(|| Some(format!("http://127.0.0.1:{skills_port}")));
+                // Only the main server port is forwarded through the tunnel
+                // (`tunnel_connect` returns a single `forwarded_port`), so a
+                // local `http://127.0.0.1:{skills_port}` fallback would be
+                // unreachable in tunnel mode. Hide the skills feature there
+                // unless the user explicitly pointed it at a reachable URL.
+                let skills_base_url = skills_base_override.clone().or_else(|| {
+                    if tunnel_mode {
+                        None
+                    } else {
+                        Some(format!("http://127.0.0.1:{skills_port}"))
+                    }
+                });
                 let port_json = serde_json::to_string(&port).unwrap_or_else(|_| "null".to_string());
                 let skills_port_json =
                     serde_json::to_string(&skills_port).unwrap_or_else(|_| "null".to_string());
@@ -421,8 +448,6 @@ pub fn run() {
                 }
 
                 window_builder.build().expect("Failed to create window");
-
-                app.manage(ServerState(Arc::new(Mutex::new(child))));
             });
 
             Ok(())
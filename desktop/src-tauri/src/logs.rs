@@ -0,0 +1,276 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{path::BaseDirectory, AppHandle, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+pub const MAX_LOG_ENTRIES: usize = 200;
+const LOG_DIR: &str = "logs";
+const MAX_LOG_FILES: usize = 10;
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+impl LogStream {
+    fn tag(self) -> &'static str {
+        match self {
+            LogStream::Stdout => "STDOUT",
+            LogStream::Stderr => "STDERR",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Unknown,
+}
+
+impl LogLevel {
+    fn severity(self) -> u8 {
+        match self {
+            LogLevel::Debug => 0,
+            LogLevel::Info | LogLevel::Unknown => 1,
+            LogLevel::Warn => 2,
+            LogLevel::Error => 3,
+        }
+    }
+
+    /// Parses a common level prefix (INFO/WARN/ERROR/DEBUG) out of a line
+    /// of sidecar output. Falls back to `Unknown` when nothing matches.
+    fn parse(line: &str) -> Self {
+        let upper = line.trim_start().trim_start_matches('[').to_ascii_uppercase();
+        if upper.starts_with("ERROR") {
+            LogLevel::Error
+        } else if upper.starts_with("WARN") {
+            LogLevel::Warn
+        } else if upper.starts_with("DEBUG") {
+            LogLevel::Debug
+        } else if upper.starts_with("INFO") {
+            LogLevel::Info
+        } else {
+            LogLevel::Unknown
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct LogEntry {
+    pub timestamp_ms: u128,
+    pub stream: LogStream,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+/// Buffers raw bytes from a single stdout/stderr stream until a full line
+/// (terminated by `\n`, with any trailing `\r` stripped) is available.
+/// Sidecar output arrives in arbitrary chunks, so a chunk boundary can land
+/// in the middle of a line.
+#[derive(Default)]
+pub struct LineAssembler {
+    buffer: Vec<u8>,
+}
+
+impl LineAssembler {
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut lines = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let mut line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            lines.push(String::from_utf8_lossy(&line).into_owned());
+        }
+
+        lines
+    }
+}
+
+struct LogBuffer {
+    entries: VecDeque<LogEntry>,
+    file: Option<File>,
+}
+
+#[derive(Clone)]
+pub struct LogState(Arc<Mutex<LogBuffer>>);
+
+impl LogState {
+    pub fn new(app: &AppHandle) -> Self {
+        Self(Arc::new(Mutex::new(LogBuffer {
+            entries: VecDeque::new(),
+            file: open_session_log_file(app),
+        })))
+    }
+}
+
+fn log_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .resolve(LOG_DIR, BaseDirectory::AppLocalData)
+        .ok()
+}
+
+/// Keeps at most `MAX_LOG_FILES` rotating session logs, deleting the oldest
+/// ones first.
+fn rotate_log_files(dir: &PathBuf) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut files: Vec<_> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "log"))
+        .collect();
+    files.sort_by_key(|entry| entry.file_name());
+
+    while files.len() >= MAX_LOG_FILES {
+        let oldest = files.remove(0);
+        let _ = fs::remove_file(oldest.path());
+    }
+}
+
+fn open_session_log_file(app: &AppHandle) -> Option<File> {
+    let dir = log_dir(app)?;
+    fs::create_dir_all(&dir).ok()?;
+    rotate_log_files(&dir);
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_millis();
+    let path = dir.join(format!("session-{timestamp}.log"));
+    OpenOptions::new().create(true).append(true).open(path).ok()
+}
+
+/// Records one already-assembled line into the in-memory ring buffer and
+/// appends it to the on-disk session log.
+pub fn record_line(log_state: &LogState, stream: LogStream, message: &str) {
+    let level = LogLevel::parse(message);
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+
+    let entry = LogEntry {
+        timestamp_ms,
+        stream,
+        level,
+        message: message.to_string(),
+    };
+
+    let Ok(mut buffer) = log_state.0.lock() else {
+        return;
+    };
+
+    if let Some(file) = buffer.file.as_mut() {
+        let _ = writeln!(file, "[{}] {}", entry.stream.tag(), entry.message);
+    }
+
+    buffer.entries.push_back(entry);
+    while buffer.entries.len() > MAX_LOG_ENTRIES {
+        buffer.entries.pop_front();
+    }
+}
+
+#[derive(Default, Deserialize)]
+pub struct LogFilter {
+    pub min_level: Option<LogLevel>,
+    pub stream: Option<LogStream>,
+    pub contains: Option<String>,
+    pub max_count: Option<usize>,
+}
+
+fn matches_filter(entry: &LogEntry, filter: &LogFilter) -> bool {
+    if let Some(min_level) = filter.min_level {
+        if entry.level.severity() < min_level.severity() {
+            return false;
+        }
+    }
+
+    if let Some(stream) = filter.stream {
+        if entry.stream != stream {
+            return false;
+        }
+    }
+
+    if let Some(contains) = &filter.contains {
+        if !entry.message.contains(contains.as_str()) {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn filtered_entries(log_state: &LogState, filter: &LogFilter) -> Result<Vec<LogEntry>, String> {
+    let buffer = log_state
+        .0
+        .lock()
+        .map_err(|_| "Failed to acquire log lock")?;
+
+    let mut entries: Vec<LogEntry> = buffer
+        .entries
+        .iter()
+        .filter(|entry| matches_filter(entry, filter))
+        .cloned()
+        .collect();
+
+    if let Some(max_count) = filter.max_count {
+        let excess = entries.len().saturating_sub(max_count);
+        entries.drain(..excess);
+    }
+
+    Ok(entries)
+}
+
+#[tauri::command]
+pub fn get_logs(app: AppHandle, filter: Option<LogFilter>) -> Result<Vec<LogEntry>, String> {
+    let log_state = app.try_state::<LogState>().ok_or("Log state not found")?;
+    filtered_entries(&log_state, &filter.unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn copy_logs_to_clipboard(
+    app: AppHandle,
+    filter: Option<LogFilter>,
+) -> Result<(), String> {
+    let log_state = app.try_state::<LogState>().ok_or("Log state not found")?;
+    let entries = filtered_entries(&log_state, &filter.unwrap_or_default())?;
+
+    let log_text = entries
+        .iter()
+        .map(|entry| format!("[{}] {}\n", entry.stream.tag(), entry.message))
+        .collect::<String>();
+
+    app.clipboard()
+        .write_text(log_text)
+        .map_err(|e| format!("Failed to copy to clipboard: {}", e))
+}
+
+#[tauri::command]
+pub fn clear_logs(app: AppHandle) -> Result<(), String> {
+    let log_state = app.try_state::<LogState>().ok_or("Log state not found")?;
+    let mut buffer = log_state
+        .0
+        .lock()
+        .map_err(|_| "Failed to acquire log lock")?;
+    buffer.entries.clear();
+    Ok(())
+}
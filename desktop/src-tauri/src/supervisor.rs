@@ -0,0 +1,252 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogResult};
+use tauri_plugin_shell::process::CommandEvent;
+use tokio::sync::mpsc::Receiver;
+
+use crate::{
+    is_server_running,
+    logs::{self, LogState, LogStream},
+    spawn_sidecar_process,
+    workspace::ServerRegistry,
+};
+
+const BASE_RESTART_DELAY: Duration = Duration::from_millis(200);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+const STABILITY_WINDOW: Duration = Duration::from_secs(10);
+const MAX_RESTARTS_PER_WINDOW: u32 = 5;
+const RESTART_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SupervisorPhase {
+    Running,
+    Restarting,
+    Failed,
+}
+
+#[derive(Clone, Serialize)]
+pub struct SupervisorSnapshot {
+    pub session_id: String,
+    pub phase: SupervisorPhase,
+    pub restart_count: u32,
+    pub last_exit_code: Option<i32>,
+}
+
+/// Supervisor snapshots keyed by workspace session id, so concurrently open
+/// workspaces don't clobber each other's restart/failed state the way a
+/// single app-wide snapshot would.
+#[derive(Clone)]
+pub struct SupervisorState(Arc<Mutex<HashMap<String, SupervisorSnapshot>>>);
+
+impl SupervisorState {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(HashMap::new())))
+    }
+}
+
+fn publish(app: &AppHandle, state: &SupervisorState, snapshot: SupervisorSnapshot) {
+    state
+        .0
+        .lock()
+        .expect("Failed to acquire supervisor lock")
+        .insert(snapshot.session_id.clone(), snapshot.clone());
+    let _ = app.emit("sidecar-supervisor-state", snapshot);
+}
+
+/// Drops a workspace's snapshot once it's gone, so a closed workspace's
+/// last-known phase doesn't linger and get served back by
+/// `get_sidecar_status`.
+fn forget(state: &SupervisorState, session_id: &str) {
+    state
+        .0
+        .lock()
+        .expect("Failed to acquire supervisor lock")
+        .remove(session_id);
+}
+
+#[tauri::command]
+pub fn get_sidecar_status(
+    app: AppHandle,
+    session_id: String,
+) -> Result<SupervisorSnapshot, String> {
+    let state = app.state::<SupervisorState>();
+    let snapshots = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to acquire supervisor lock")?;
+    snapshots
+        .get(&session_id)
+        .cloned()
+        .ok_or_else(|| format!("No supervisor state for workspace {session_id}"))
+}
+
+/// Consumes the sidecar's stdout/stderr into `LogState` until it sees
+/// `CommandEvent::Terminated`, whose exit code is returned. Each stream
+/// gets its own line assembler since chunk boundaries don't align with
+/// line boundaries.
+async fn drain_until_exit(rx: &mut Receiver<CommandEvent>, log_state: &LogState) -> Option<i32> {
+    let mut stdout_assembler = logs::LineAssembler::default();
+    let mut stderr_assembler = logs::LineAssembler::default();
+
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(bytes) => {
+                for line in stdout_assembler.push(&bytes) {
+                    println!("{line}");
+                    logs::record_line(log_state, LogStream::Stdout, &line);
+                }
+            }
+            CommandEvent::Stderr(bytes) => {
+                for line in stderr_assembler.push(&bytes) {
+                    eprintln!("{line}");
+                    logs::record_line(log_state, LogStream::Stderr, &line);
+                }
+            }
+            CommandEvent::Terminated(payload) => return payload.code,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Watches one workspace's sidecar for the rest of its lifetime: forwards
+/// its logs, and on unexpected exit respawns it with capped exponential
+/// backoff. Gives up once the sidecar has exited more than
+/// `MAX_RESTARTS_PER_WINDOW` times within `RESTART_WINDOW`: it publishes
+/// `Failed`, shows the failure dialog, then closes the workspace and drops
+/// its snapshot. Returns the task handle so the caller can abort it
+/// (e.g. `close_workspace`) instead of leaving it to respawn a sidecar
+/// whose workspace is already gone.
+pub fn watch(
+    app: AppHandle,
+    registry: ServerRegistry,
+    session_id: String,
+    port: u32,
+    skills_port: u32,
+    mut rx: Receiver<CommandEvent>,
+) -> tauri::async_runtime::JoinHandle<()> {
+    tauri::async_runtime::spawn(async move {
+        let supervisor_state = app.state::<SupervisorState>().inner().clone();
+        let log_state = app.state::<LogState>().inner().clone();
+        let cwd = registry.cwd(&session_id);
+
+        let mut consecutive_failures: u32 = 0;
+        let mut restarts_in_window: u32 = 0;
+        let mut window_start = Instant::now();
+
+        loop {
+            let exit_code = drain_until_exit(&mut rx, &log_state).await;
+            println!("Sidecar terminated with code {:?}", exit_code);
+
+            if !registry.contains(&session_id) {
+                println!("Workspace {session_id} was closed; not respawning its sidecar");
+                forget(&supervisor_state, &session_id);
+                return;
+            }
+
+            if window_start.elapsed() > RESTART_WINDOW {
+                restarts_in_window = 0;
+                window_start = Instant::now();
+            }
+            restarts_in_window += 1;
+
+            if restarts_in_window > MAX_RESTARTS_PER_WINDOW {
+                publish(
+                    &app,
+                    &supervisor_state,
+                    SupervisorSnapshot {
+                        session_id: session_id.clone(),
+                        phase: SupervisorPhase::Failed,
+                        restart_count: consecutive_failures,
+                        last_exit_code: exit_code,
+                    },
+                );
+
+                let res = app.dialog()
+                  .message("The OpenCode Server for this workspace keeps crashing and could not be kept alive. Copy logs using the button below and send them to the team for assistance. This workspace will be closed; your other open workspaces are unaffected.")
+                  .title("Workspace Startup Failed")
+                  .buttons(MessageDialogButtons::OkCancelCustom("Copy Logs And Close".to_string(), "Close Workspace".to_string()))
+                  .blocking_show_with_result();
+
+                if matches!(&res, MessageDialogResult::Custom(name) if name == "Copy Logs And Close")
+                {
+                    match logs::copy_logs_to_clipboard(app.clone(), None).await {
+                        Ok(()) => println!("Logs copied to clipboard successfully"),
+                        Err(e) => println!("Failed to copy logs to clipboard: {}", e),
+                    }
+                }
+
+                // Tear down only the crash-looping workspace; this task is the
+                // one running the teardown, so it can't abort its own handle
+                // the way `close_workspace` aborts a workspace's supervisor on
+                // user-initiated close — `remove_and_kill` leaves that to us
+                // simply returning below.
+                registry.remove_and_kill(&session_id);
+                forget(&supervisor_state, &session_id);
+                return;
+            }
+
+            publish(
+                &app,
+                &supervisor_state,
+                SupervisorSnapshot {
+                    session_id: session_id.clone(),
+                    phase: SupervisorPhase::Restarting,
+                    restart_count: consecutive_failures,
+                    last_exit_code: exit_code,
+                },
+            );
+
+            let delay = BASE_RESTART_DELAY
+                .saturating_mul(1 << consecutive_failures.min(20))
+                .min(MAX_RESTART_DELAY);
+            tokio::time::sleep(delay).await;
+
+            let (new_rx, new_child) =
+                spawn_sidecar_process(&app, port, skills_port, cwd.as_deref());
+            rx = new_rx;
+            registry.set_child(&session_id, new_child);
+            consecutive_failures += 1;
+
+            if wait_for_stable_server(port).await {
+                consecutive_failures = 0;
+                restarts_in_window = 0;
+                publish(
+                    &app,
+                    &supervisor_state,
+                    SupervisorSnapshot {
+                        session_id: session_id.clone(),
+                        phase: SupervisorPhase::Running,
+                        restart_count: 0,
+                        last_exit_code: None,
+                    },
+                );
+            }
+        }
+    })
+}
+
+/// Polls `port` until `STABILITY_WINDOW` has fully elapsed, bailing out on
+/// the first failed check. A sidecar only counts as stable if it stays
+/// reachable for the *entire* window, not just at some point within it —
+/// otherwise a sidecar that binds its port early and crashes shortly after
+/// would pass every single restart, and `consecutive_failures` /
+/// `restarts_in_window` would never accumulate toward `MAX_RESTARTS_PER_WINDOW`.
+async fn wait_for_stable_server(port: u32) -> bool {
+    let deadline = Instant::now() + STABILITY_WINDOW;
+    while Instant::now() < deadline {
+        if !is_server_running(port).await {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    true
+}
@@ -0,0 +1,307 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+use tokio_rustls::{
+    client::TlsStream,
+    rustls::{pki_types::ServerName, ClientConfig, RootCertStore},
+    TlsConnector,
+};
+
+use crate::find_free_port;
+
+/// Relay connection, wrapped in TLS once the handshake below completes. The
+/// handshake exchanges a long-lived token, so it must never go out over a
+/// plaintext socket.
+type RelayStream = TlsStream<TcpStream>;
+
+/// Builds (and caches) the TLS connector from the OS trust store. Falls
+/// back to a cached error rather than panicking when the trust store can't
+/// be read (e.g. a minimal container without `ca-certificates`), so this
+/// surfaces through the same `Result`/dialog path as every other relay
+/// failure instead of aborting the task outright.
+fn tls_connector() -> Result<&'static TlsConnector, String> {
+    static CONNECTOR: OnceLock<Result<TlsConnector, String>> = OnceLock::new();
+    CONNECTOR
+        .get_or_init(|| {
+            let certs = rustls_native_certs::load_native_certs()
+                .map_err(|e| format!("Failed to load native certs: {e}"))?;
+
+            let mut roots = RootCertStore::empty();
+            for cert in certs {
+                let _ = roots.add(cert);
+            }
+
+            let config = ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+
+            Ok(TlsConnector::from(Arc::new(config)))
+        })
+        .as_ref()
+        .map_err(Clone::clone)
+}
+
+/// Opens a TLS connection to the relay. `relay_addr` is `host:port`; the
+/// host half is used both for the TCP connect and as the TLS server name
+/// to validate the relay's certificate against.
+async fn connect_relay(relay_addr: &str) -> Result<RelayStream, String> {
+    let host = relay_addr
+        .rsplit_once(':')
+        .map(|(host, _)| host)
+        .unwrap_or(relay_addr)
+        .to_string();
+
+    let tcp = TcpStream::connect(relay_addr)
+        .await
+        .map_err(|e| format!("Failed to reach relay {relay_addr}: {e}"))?;
+
+    let server_name = ServerName::try_from(host)
+        .map_err(|e| format!("Invalid relay host in {relay_addr}: {e}"))?;
+
+    tls_connector()?
+        .connect(server_name, tcp)
+        .await
+        .map_err(|e| format!("TLS handshake with relay {relay_addr} failed: {e}"))
+}
+
+/// Relay address + connection token needed to attach to a remote
+/// `opencode serve`, sourced from the environment so the app can be
+/// pointed at a tunnel without any UI.
+pub struct TunnelConfig {
+    pub relay_addr: String,
+    pub token: String,
+}
+
+pub fn get_tunnel_config() -> Option<TunnelConfig> {
+    let relay_addr = crate::env_string("OPENCODE_DESKTOP_TUNNEL_RELAY")?;
+    let token = crate::env_string("OPENCODE_DESKTOP_TUNNEL_TOKEN")?;
+    Some(TunnelConfig { relay_addr, token })
+}
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum TunnelStatus {
+    Disconnected,
+    Connecting,
+    Connected { forwarded_port: u32 },
+    Failed { reason: String },
+}
+
+impl Default for TunnelStatus {
+    fn default() -> Self {
+        TunnelStatus::Disconnected
+    }
+}
+
+#[derive(Default)]
+struct TunnelInner {
+    status: TunnelStatus,
+    forward_task: Option<tauri::async_runtime::JoinHandle<()>>,
+    control_task: Option<tauri::async_runtime::JoinHandle<()>>,
+}
+
+#[derive(Clone)]
+pub struct TunnelState(Arc<Mutex<TunnelInner>>);
+
+impl TunnelState {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(TunnelInner::default())))
+    }
+}
+
+fn set_status(state: &TunnelState, status: TunnelStatus) {
+    state
+        .0
+        .lock()
+        .expect("Failed to acquire tunnel lock")
+        .status = status;
+}
+
+/// Aborts the previous forward loop (and, with it, closes the listener it
+/// owns) and control connection so a reconnect or disconnect never leaves
+/// an old forwarded port proxying traffic, or an old relay session, in the
+/// background.
+fn abort_tunnel_tasks(state: &TunnelState) {
+    let mut inner = state.0.lock().expect("Failed to acquire tunnel lock");
+    if let Some(task) = inner.forward_task.take() {
+        task.abort();
+    }
+    if let Some(task) = inner.control_task.take() {
+        task.abort();
+    }
+}
+
+/// Keeps the control connection from `authenticate` open for the life of
+/// the tunnel: the relay may tie `session_id` to this connection, tearing
+/// the session down the instant it closes, so it must outlive the initial
+/// handshake rather than being dropped once `tunnel_connect` returns.
+async fn hold_control_connection(session_id: String, mut relay: RelayStream) {
+    let mut discard = [0u8; 256];
+    loop {
+        match relay.read(&mut discard).await {
+            Ok(0) => {
+                eprintln!("Tunnel: control connection for session {session_id} closed by relay");
+                return;
+            }
+            Ok(_) => continue,
+            Err(e) => {
+                eprintln!("Tunnel: control connection for session {session_id} errored: {e}");
+                return;
+            }
+        }
+    }
+}
+
+/// Speaks the relay's line-based handshake over TLS: send `CONNECT <token>`,
+/// expect back `OK <session id>` before any data can be forwarded. The
+/// token never goes out until the TLS handshake above has validated the
+/// relay's certificate.
+async fn authenticate(relay_addr: &str, token: &str) -> Result<(RelayStream, String), String> {
+    let mut relay = connect_relay(relay_addr).await?;
+
+    relay
+        .write_all(format!("CONNECT {token}\n").as_bytes())
+        .await
+        .map_err(|e| format!("Failed to send relay handshake: {e}"))?;
+
+    let mut line = String::new();
+    BufReader::new(&mut relay)
+        .read_line(&mut line)
+        .await
+        .map_err(|e| format!("Failed to read relay handshake: {e}"))?;
+
+    let session_id = line
+        .trim()
+        .strip_prefix("OK ")
+        .map(str::to_string)
+        .ok_or_else(|| format!("Relay rejected connection: {}", line.trim()))?;
+
+    Ok((relay, session_id))
+}
+
+/// Accepts local connections on the forwarded port and relays each one to
+/// the remote `opencode serve` through a fresh authenticated connection to
+/// the relay, tagged with the session id from the initial handshake.
+async fn forward_loop(
+    relay_addr: String,
+    token: String,
+    session_id: String,
+    listener: TcpListener,
+) {
+    loop {
+        let Ok((mut local, _)) = listener.accept().await else {
+            break;
+        };
+
+        let relay_addr = relay_addr.clone();
+        let token = token.clone();
+        let session_id = session_id.clone();
+
+        tauri::async_runtime::spawn(async move {
+            let (mut relay, _) = match authenticate(&relay_addr, &token).await {
+                Ok(pair) => pair,
+                Err(e) => {
+                    eprintln!("Tunnel: failed to open relay stream for session {session_id}: {e}");
+                    return;
+                }
+            };
+
+            if let Err(e) = relay
+                .write_all(format!("DATA {session_id}\n").as_bytes())
+                .await
+            {
+                eprintln!("Tunnel: failed to attach to session {session_id}: {e}");
+                return;
+            }
+
+            if let Err(e) = tokio::io::copy_bidirectional(&mut local, &mut relay).await {
+                eprintln!("Tunnel: stream for session {session_id} closed: {e}");
+            }
+        });
+    }
+}
+
+/// Connects to `relay_addr`, exchanges `token` for a session id, then binds
+/// a local port that forwards to the remote server through the relay.
+#[tauri::command]
+pub async fn tunnel_connect(
+    app: AppHandle,
+    relay_addr: String,
+    token: String,
+) -> Result<TunnelStatus, String> {
+    let tunnel_state = app.state::<TunnelState>();
+    abort_tunnel_tasks(&tunnel_state);
+    set_status(&tunnel_state, TunnelStatus::Connecting);
+
+    let (relay, session_id) = match authenticate(&relay_addr, &token).await {
+        Ok(pair) => pair,
+        Err(e) => {
+            set_status(&tunnel_state, TunnelStatus::Failed { reason: e.clone() });
+            return Err(e);
+        }
+    };
+
+    let forwarded_port = find_free_port();
+    let listener = match TcpListener::bind(("127.0.0.1", forwarded_port as u16)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            let reason = format!("Failed to bind forwarded port: {e}");
+            set_status(
+                &tunnel_state,
+                TunnelStatus::Failed {
+                    reason: reason.clone(),
+                },
+            );
+            return Err(reason);
+        }
+    };
+
+    let control_task =
+        tauri::async_runtime::spawn(hold_control_connection(session_id.clone(), relay));
+    let forward_task =
+        tauri::async_runtime::spawn(forward_loop(relay_addr, token, session_id, listener));
+
+    {
+        let mut inner = tunnel_state
+            .0
+            .lock()
+            .expect("Failed to acquire tunnel lock");
+        inner.control_task = Some(control_task);
+        inner.forward_task = Some(forward_task);
+    }
+
+    let status = TunnelStatus::Connected { forwarded_port };
+    set_status(&tunnel_state, status.clone());
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn tunnel_status(app: AppHandle) -> Result<TunnelStatus, String> {
+    let tunnel_state = app.state::<TunnelState>();
+    let status = tunnel_state
+        .0
+        .lock()
+        .map_err(|_| "Failed to acquire tunnel lock")?
+        .status
+        .clone();
+    Ok(status)
+}
+
+/// Tears down the forward loop (closing its listener) and control
+/// connection in addition to flipping the status, so a "disconnected"
+/// tunnel accepts no new local connections and ends the relay session
+/// instead of leaving either running in the background. Streams that the
+/// forward loop already accepted are not tracked; they run until either
+/// end closes them.
+#[tauri::command]
+pub fn tunnel_disconnect(app: AppHandle) -> Result<(), String> {
+    let tunnel_state = app.state::<TunnelState>();
+    abort_tunnel_tasks(&tunnel_state);
+    set_status(&tunnel_state, TunnelStatus::Disconnected);
+    Ok(())
+}
@@ -0,0 +1,251 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_shell::process::CommandChild;
+
+use crate::{find_free_port, spawn_sidecar_process, supervisor};
+
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
+struct Workspace {
+    child: CommandChild,
+    port: u32,
+    skills_port: u32,
+    cwd: PathBuf,
+    supervisor: Option<tauri::async_runtime::JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct RegistryInner {
+    workspaces: HashMap<String, Workspace>,
+    active: Option<String>,
+}
+
+/// Tracks every running `opencode-cli` sidecar, keyed by session id, so the
+/// app can have one backend per open project instead of a single global
+/// one.
+#[derive(Clone)]
+pub struct ServerRegistry(Arc<Mutex<RegistryInner>>);
+
+impl ServerRegistry {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(RegistryInner::default())))
+    }
+
+    /// Registers an already-spawned sidecar under a fresh session id. The
+    /// first workspace registered becomes the active one. Used both for
+    /// the workspace opened at startup and for `open_workspace`.
+    pub fn insert(&self, child: CommandChild, port: u32, skills_port: u32, cwd: PathBuf) -> String {
+        let session_id = format!("ws-{}", NEXT_SESSION_ID.fetch_add(1, Ordering::SeqCst));
+
+        let mut inner = self.0.lock().expect("Failed to acquire registry lock");
+        inner.workspaces.insert(
+            session_id.clone(),
+            Workspace {
+                child,
+                port,
+                skills_port,
+                cwd,
+                supervisor: None,
+            },
+        );
+        inner.active.get_or_insert_with(|| session_id.clone());
+
+        session_id
+    }
+
+    /// Attaches the `supervisor::watch` task for a workspace so it can be
+    /// aborted when the workspace is closed. Set right after `insert`,
+    /// once the supervisor has been spawned.
+    pub fn set_supervisor_handle(
+        &self,
+        session_id: &str,
+        handle: tauri::async_runtime::JoinHandle<()>,
+    ) {
+        if let Ok(mut inner) = self.0.lock() {
+            if let Some(workspace) = inner.workspaces.get_mut(session_id) {
+                workspace.supervisor = Some(handle);
+            }
+        }
+    }
+
+    /// Swaps in a freshly respawned child for a workspace that the
+    /// supervisor just restarted.
+    pub fn set_child(&self, session_id: &str, child: CommandChild) {
+        if let Ok(mut inner) = self.0.lock() {
+            if let Some(workspace) = inner.workspaces.get_mut(session_id) {
+                workspace.child = child;
+            }
+        }
+    }
+
+    pub fn cwd(&self, session_id: &str) -> Option<PathBuf> {
+        let inner = self.0.lock().expect("Failed to acquire registry lock");
+        inner.workspaces.get(session_id).map(|ws| ws.cwd.clone())
+    }
+
+    /// Whether `session_id` is still tracked. The supervisor checks this
+    /// after an unexpected exit so it doesn't respawn a sidecar whose
+    /// workspace was closed out from under it.
+    pub fn contains(&self, session_id: &str) -> bool {
+        let inner = self.0.lock().expect("Failed to acquire registry lock");
+        inner.workspaces.contains_key(session_id)
+    }
+
+    /// Removes `session_id` and kills its sidecar, without touching its
+    /// supervisor handle. Used by the supervisor's own give-up path, which
+    /// runs inside that very task and so can't `abort()` itself the way
+    /// `close_workspace` aborts a workspace's supervisor on user-initiated
+    /// close.
+    pub fn remove_and_kill(&self, session_id: &str) {
+        let mut inner = self.0.lock().expect("Failed to acquire registry lock");
+        let Some(workspace) = inner.workspaces.remove(session_id) else {
+            return;
+        };
+        let _ = workspace.child.kill();
+
+        if inner.active.as_deref() == Some(session_id) {
+            inner.active = inner.workspaces.keys().next().cloned();
+        }
+    }
+
+    /// Kills every tracked sidecar and aborts its supervisor. Called on
+    /// `kill_sidecar` / app exit so no workspace is left running in the
+    /// background.
+    pub fn kill_all(&self) {
+        let mut inner = self.0.lock().expect("Failed to acquire registry lock");
+        for (_, workspace) in inner.workspaces.drain() {
+            if let Some(supervisor) = workspace.supervisor {
+                supervisor.abort();
+            }
+            let _ = workspace.child.kill();
+        }
+        inner.active = None;
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct WorkspaceSummary {
+    pub id: String,
+    pub cwd: PathBuf,
+    pub port: u32,
+    pub skills_port: u32,
+    pub active: bool,
+}
+
+fn summary(id: &str, workspace: &Workspace, active: Option<&str>) -> WorkspaceSummary {
+    WorkspaceSummary {
+        id: id.to_string(),
+        cwd: workspace.cwd.clone(),
+        port: workspace.port,
+        skills_port: workspace.skills_port,
+        active: active == Some(id),
+    }
+}
+
+/// Spawns a new `opencode-cli` sidecar rooted at `path` and starts
+/// supervising it. Returns the new session's summary so the frontend can
+/// route `baseUrl`/`skillsBase` to it.
+#[tauri::command]
+pub async fn open_workspace(app: AppHandle, path: String) -> Result<WorkspaceSummary, String> {
+    let registry = app.state::<ServerRegistry>();
+    let cwd = PathBuf::from(path);
+
+    let port = find_free_port();
+    let mut skills_port = find_free_port();
+    while skills_port == port {
+        skills_port = find_free_port();
+    }
+
+    let (rx, child) = spawn_sidecar_process(&app, port, skills_port, Some(&cwd));
+    let session_id = registry.insert(child, port, skills_port, cwd.clone());
+
+    let handle = supervisor::watch(
+        app.clone(),
+        registry.inner().clone(),
+        session_id.clone(),
+        port,
+        skills_port,
+        rx,
+    );
+    registry.set_supervisor_handle(&session_id, handle);
+
+    let inner = registry
+        .0
+        .lock()
+        .map_err(|_| "Failed to acquire registry lock")?;
+    let workspace = inner
+        .workspaces
+        .get(&session_id)
+        .ok_or("Workspace vanished right after it was opened")?;
+
+    Ok(summary(&session_id, workspace, inner.active.as_deref()))
+}
+
+#[tauri::command]
+pub fn list_workspaces(app: AppHandle) -> Result<Vec<WorkspaceSummary>, String> {
+    let registry = app.state::<ServerRegistry>();
+    let inner = registry
+        .0
+        .lock()
+        .map_err(|_| "Failed to acquire registry lock")?;
+
+    Ok(inner
+        .workspaces
+        .iter()
+        .map(|(id, workspace)| summary(id, workspace, inner.active.as_deref()))
+        .collect())
+}
+
+/// Kills just the named workspace's sidecar and drops it from the
+/// registry. If it was the active workspace, another open one (if any)
+/// becomes active.
+#[tauri::command]
+pub fn close_workspace(app: AppHandle, id: String) -> Result<(), String> {
+    let registry = app.state::<ServerRegistry>();
+    let mut inner = registry
+        .0
+        .lock()
+        .map_err(|_| "Failed to acquire registry lock")?;
+
+    let Some(workspace) = inner.workspaces.remove(&id) else {
+        return Ok(());
+    };
+    if let Some(supervisor) = workspace.supervisor {
+        supervisor.abort();
+    }
+    let _ = workspace.child.kill();
+
+    if inner.active.as_deref() == Some(id.as_str()) {
+        inner.active = inner.workspaces.keys().next().cloned();
+    }
+
+    Ok(())
+}
+
+/// Marks `id` as the active workspace and returns its connection details
+/// so the frontend can repoint `baseUrl`/`skillsBase` at it.
+#[tauri::command]
+pub fn set_active_workspace(app: AppHandle, id: String) -> Result<WorkspaceSummary, String> {
+    let registry = app.state::<ServerRegistry>();
+    let mut inner = registry
+        .0
+        .lock()
+        .map_err(|_| "Failed to acquire registry lock")?;
+
+    if !inner.workspaces.contains_key(&id) {
+        return Err(format!("No workspace with id {id}"));
+    }
+    inner.active = Some(id.clone());
+
+    let workspace = inner.workspaces.get(&id).expect("Checked above");
+    Ok(summary(&id, workspace, Some(id.as_str())))
+}